@@ -16,30 +16,14 @@ pub fn konfig_section_derive(input: TokenStream) -> TokenStream {
             }
             fn validate(&self) -> Result<(), KonfigError> { Ok(()) }
             fn on_load(&self) -> Result<(), KonfigError> { Ok(()) }
-            // fn to_bytes(&self, format: &FormatHandlerEnum) -> Result<Vec<u8>, KonfigError> {
-            //     format.marshal(self)
-            // }
-            // fn update_from_bytes(&mut self, bytes: &[u8], format: &FormatHandlerEnum) -> Result<(), KonfigError> {
-            //     let new_instance: #name = match format {
-            //         FormatHandlerEnum::JSON(_) => {
-            //             serde_json::from_slice(bytes)
-            //                 .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?
-            //         },
-            //         FormatHandlerEnum::YAML(_) => {
-            //             serde_yaml::from_slice(bytes)
-            //                 .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?
-            //         },
-            //         FormatHandlerEnum::TOML(_) => {
-            //             let s = std::str::from_utf8(bytes)
-            //                 .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?;
-            //             toml::from_str(s)
-            //                 .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?
-            //         },
-            //     };
-            //
-            //     *self = new_instance;
-            //     Ok(())
-            // }
+            fn to_bytes(&self, format: &FormatHandler) -> Result<Vec<u8>, KonfigError> {
+                // let the handler pick the encoding so custom formats serialize like the builtins
+                format.marshal(self)
+            }
+            fn update_from_bytes(&mut self, bytes: &[u8], format: &FormatHandler) -> Result<(), KonfigError> {
+                *self = format.unmarshal::<#name>(bytes)?;
+                Ok(())
+            }
         }
     };
 
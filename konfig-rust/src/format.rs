@@ -2,12 +2,18 @@ use std::any::Any;
 use crate::KonfigError;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::path::Path;
 use std::str;
+use std::sync::{Arc, Mutex, OnceLock};
 
+#[derive(Clone)]
 pub enum Format {
     JSON,
     YAML,
     TOML,
+    /// A custom format registered under the given file extension via [`register_format`].
+    Custom(String),
 }
 
 impl Format {
@@ -16,17 +22,78 @@ impl Format {
             Format::JSON => FormatHandler::Builtin(BuiltinFormat::JSON),
             Format::YAML => FormatHandler::Builtin(BuiltinFormat::YAML),
             Format::TOML => FormatHandler::Builtin(BuiltinFormat::TOML),
+            // look the factory up fresh each time so a handler is never shared across managers;
+            // an unregistered extension should never reach here via `from_path`, but fall back to
+            // JSON rather than panic if one is constructed by hand
+            Format::Custom(ext) => match registry().lock().unwrap().get(ext) {
+                Some(factory) => FormatHandler::Custom(factory()),
+                None => FormatHandler::Builtin(BuiltinFormat::JSON),
+            },
         }
     }
+
+    /// Auto-selects a format from a path's extension.
+    ///
+    /// The JSON/YAML/TOML builtins are always recognised (`.json`, `.yaml`/`.yml`, `.toml`), and
+    /// any extension registered with [`register_format`] resolves to its custom handler. Returns
+    /// `None` for a missing or unknown extension so callers can fall back to an explicit format.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Format> {
+        let ext = path
+            .as_ref()
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+
+        match ext.as_str() {
+            "json" => Some(Format::JSON),
+            "yaml" | "yml" => Some(Format::YAML),
+            "toml" => Some(Format::TOML),
+            other if registry().lock().unwrap().contains_key(other) => {
+                Some(Format::Custom(other.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Builds a fresh [`ConfigFormat`] for a registered extension.
+type FormatFactory = Arc<dyn Fn() -> Box<dyn ConfigFormat> + Send + Sync>;
+
+/// Process-global map of file extension -> custom format factory.
+fn registry() -> &'static Mutex<HashMap<String, FormatFactory>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<String, FormatFactory>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registers a custom [`ConfigFormat`], produced by `factory`, for one or more file extensions
+/// given without the leading dot (e.g. `["ini"]` or `["ron"]`).
+///
+/// Once registered, [`Format::from_path`] picks the handler up automatically, so a user shipping an
+/// INI or RON format can wire it in once and have `KonfigManager` load and save through it instead
+/// of being forced through the JSON round-trip.
+pub fn register_format<F>(extensions: &[&str], factory: F)
+where
+    F: Fn() -> Box<dyn ConfigFormat> + Send + Sync + 'static,
+{
+    let factory: FormatFactory = Arc::new(factory);
+    let mut registry = registry().lock().unwrap();
+    for ext in extensions {
+        registry.insert(ext.to_ascii_lowercase(), factory.clone());
+    }
 }
 
-/// A generic trait for format handlers, implement to create a custom format
+/// A trait for format handlers, implement to create a custom format.
+///
+/// The methods work on `serde_json::Value` rather than a generic `T` so the trait stays
+/// dyn-compatible and can live behind [`FormatHandler::Custom`]'s `Box<dyn ConfigFormat>`. The
+/// manager converts between your sections and `Value` with serde, so a handler only needs to turn
+/// a `Value` into bytes and back.
 pub trait ConfigFormat {
-    /// Uses the serde `Serialize` trait to serialize data to bytes in the specified format
-    fn marshal<T: Serialize>(&self, data: &T) -> Result<Vec<u8>, KonfigError>;
+    /// Serializes an already-normalised `serde_json::Value` into bytes in the target format.
+    fn marshal_value(&self, value: &serde_json::Value) -> Result<Vec<u8>, KonfigError>;
 
-    /// Uses the serde `DeserializeOwned` trait to deserialize data from bytes in the specified format
-    fn unmarshal<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, KonfigError>;
+    /// Parses bytes in the target format back into a `serde_json::Value`.
+    fn unmarshal_value(&self, data: &[u8]) -> Result<serde_json::Value, KonfigError>;
 }
 
 // I love how this just duplicates the Format enum xd
@@ -57,7 +124,12 @@ impl FormatHandler {
                 .map_err(|err| KonfigError::MarshalError(err.to_string()))
                 .map(|s| s.into_bytes()),
 
-            FormatHandler::Custom(custom) => custom.marshal(data),
+            // bridge the generic section through a `Value` so custom handlers stay dyn-compatible
+            FormatHandler::Custom(custom) => {
+                let value = serde_json::to_value(data)
+                    .map_err(|err| KonfigError::MarshalError(err.to_string()))?;
+                custom.marshal_value(&value)
+            }
         }
     }
 
@@ -75,10 +147,53 @@ impl FormatHandler {
             )
             .map_err(|err| KonfigError::UnmarshalError(err.to_string())),
 
-            FormatHandler::Custom(custom) => custom.marshal(data).and_then(|bytes| {
-                serde_json::from_slice(&bytes)
+            FormatHandler::Custom(custom) => {
+                let value = custom.unmarshal_value(data)?;
+                serde_json::from_value(value)
                     .map_err(|err| KonfigError::UnmarshalError(err.to_string()))
-            }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path_detects_builtin_extensions() {
+        assert!(matches!(Format::from_path("config.json"), Some(Format::JSON)));
+        assert!(matches!(Format::from_path("config.yaml"), Some(Format::YAML)));
+        assert!(matches!(Format::from_path("config.yml"), Some(Format::YAML)));
+        assert!(matches!(Format::from_path("config.toml"), Some(Format::TOML)));
+        assert!(Format::from_path("config.unknownext").is_none());
+        assert!(Format::from_path("config").is_none());
+    }
+
+    struct JsonAlias;
+
+    impl ConfigFormat for JsonAlias {
+        fn marshal_value(&self, value: &serde_json::Value) -> Result<Vec<u8>, KonfigError> {
+            serde_json::to_vec(value).map_err(|err| KonfigError::MarshalError(err.to_string()))
+        }
+
+        fn unmarshal_value(&self, data: &[u8]) -> Result<serde_json::Value, KonfigError> {
+            serde_json::from_slice(data).map_err(|err| KonfigError::UnmarshalError(err.to_string()))
+        }
+    }
+
+    #[test]
+    fn from_path_detects_registered_custom_extension() {
+        register_format(&["myfmt"], || Box::new(JsonAlias));
+
+        match Format::from_path("settings.myfmt") {
+            Some(Format::Custom(ext)) => assert_eq!(ext, "myfmt"),
+            _ => panic!("expected a registered custom format"),
         }
+        // the extension lookup is case-insensitive
+        assert!(matches!(
+            Format::from_path("settings.MYFMT"),
+            Some(Format::Custom(_))
+        ));
     }
 }
@@ -0,0 +1,424 @@
+use crate::format::Format;
+use crate::KonfigError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+/// Describes where the values in a [`ConfigLayer`] came from.
+///
+/// Every resolved field remembers the origin of the layer that won it, so users can query
+/// `origin_of` to debug why a value ended up the way it did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    /// Built-in defaults baked into the program.
+    Defaults,
+    /// A file on disk at the given path.
+    File(PathBuf),
+    /// Values read from the process environment.
+    Environment,
+    /// A programmatic override set at runtime.
+    Override,
+}
+
+impl ConfigOrigin {
+    /// The default precedence rank for a layer with this origin.
+    ///
+    /// Higher ranks win when two layers set the same field, roughly mirroring the ordering
+    /// most configuration stacks use: defaults at the bottom, explicit overrides at the top.
+    pub(crate) fn rank(&self) -> i32 {
+        match self {
+            ConfigOrigin::Defaults => 0,
+            ConfigOrigin::File(_) => 100,
+            ConfigOrigin::Environment => 200,
+            ConfigOrigin::Override => 300,
+        }
+    }
+}
+
+/// A single layer in the configuration stack.
+///
+/// Layers are merged from lowest to highest `precedence` on every `load()`, deep-merging the
+/// per-section `serde_json::Value` maps so that a higher layer only shadows the exact scalars it
+/// sets rather than replacing a whole section.
+#[derive(Clone)]
+pub struct ConfigLayer {
+    /// Where this layer's values came from.
+    pub origin: ConfigOrigin,
+    /// The format used to (re)read this layer when it is file backed.
+    pub(crate) format: Format,
+    /// Resolved precedence; defaults to [`ConfigOrigin::rank`] but kept as a field so the order
+    /// is explicit once the stack is built.
+    pub(crate) precedence: i32,
+    /// Whether `save()` is allowed to write back into this layer.
+    pub(crate) writable: bool,
+    /// The active profile applied when this layer is file backed; `None` uses only the `default`
+    /// block, or the whole file when it has no `default` block (see [`resolve_profiles`]).
+    /// Non-file layers (environment, overrides) leave this unset.
+    pub(crate) profile: Option<String>,
+    /// Section name -> raw value tree for that section.
+    pub(crate) sections: Map<String, Value>,
+}
+
+impl ConfigLayer {
+    /// Builds a layer from an already-parsed section map.
+    pub(crate) fn new(origin: ConfigOrigin, format: Format, sections: Map<String, Value>) -> Self {
+        let precedence = origin.rank();
+        ConfigLayer {
+            origin,
+            format,
+            precedence,
+            writable: false,
+            profile: None,
+            sections,
+        }
+    }
+
+    /// Builds the writable primary layer that `save()` persists to, tied to `profile`.
+    pub(crate) fn writable_file(origin: ConfigOrigin, format: Format, profile: Option<String>) -> Self {
+        let mut layer = ConfigLayer::new(origin, format, Map::new());
+        layer.writable = true;
+        layer.profile = profile;
+        layer
+    }
+
+    /// Re-reads a file-backed layer from disk, collapsing its profile blocks into the section map.
+    pub(crate) fn reload(&mut self) -> Result<(), KonfigError> {
+        if let ConfigOrigin::File(path) = self.origin.clone() {
+            let raw = read_file_raw(&self.format, &path)?;
+            self.sections = resolve_profiles(&raw, self.profile.as_deref());
+        }
+        Ok(())
+    }
+}
+
+/// Brings a layer stack up to date before a merge: re-reads every file-backed layer from disk and
+/// rebuilds the (single) environment layer from scratch so vars that disappeared stop winning.
+///
+/// `format` is only used to stamp the rebuilt environment layer; it plays no part in the merge.
+pub(crate) fn refresh(
+    layers: &mut Vec<ConfigLayer>,
+    format: &Format,
+    env_prefix: Option<&str>,
+    env_separator: &str,
+) -> Result<(), KonfigError> {
+    for layer in layers.iter_mut() {
+        layer.reload()?;
+    }
+
+    layers.retain(|layer| layer.origin != ConfigOrigin::Environment);
+    if let Some(prefix) = env_prefix {
+        let sections = env_sections(prefix, env_separator);
+        layers.push(ConfigLayer::new(
+            ConfigOrigin::Environment,
+            format.clone(),
+            sections,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Merges the layer stack for each of `names`, lowest precedence first, returning the resolved
+/// value and the per-field origin map for every section that at least one layer sets.
+pub(crate) fn resolve(
+    layers: &[ConfigLayer],
+    names: &[String],
+) -> Vec<(String, Value, HashMap<String, ConfigOrigin>)> {
+    let mut order: Vec<usize> = (0..layers.len()).collect();
+    order.sort_by_key(|&i| layers[i].precedence);
+
+    let mut resolved = Vec::new();
+    for name in names {
+        let mut merged = Value::Null;
+        let mut origins: HashMap<String, ConfigOrigin> = HashMap::new();
+
+        for &i in &order {
+            if let Some(section_value) = layers[i].sections.get(name) {
+                if let Value::Object(fields) = section_value {
+                    for key in fields.keys() {
+                        origins.insert(key.clone(), layers[i].origin.clone());
+                    }
+                }
+                deep_merge(&mut merged, section_value);
+            }
+        }
+
+        if !merged.is_null() {
+            resolved.push((name.clone(), merged, origins));
+        }
+    }
+
+    resolved
+}
+
+/// Collapses a profiled file into a flat section map: the `default` block is the base and the
+/// active `profile` block (if any) is merged over it, so one file can drive several environments.
+///
+/// For backwards compatibility with profile-free files, a file that carries neither a `default`
+/// block nor the active profile's block is treated as a legacy flat `{"Section": {...}}` file: its
+/// root is used as the section map directly, so activating a profile against such a file still
+/// loads the on-disk values instead of silently resolving to an empty map.
+pub(crate) fn resolve_profiles(raw: &Map<String, Value>, profile: Option<&str>) -> Map<String, Value> {
+    match profile {
+        Some(name) => {
+            // a file with no default and no matching profile block isn't profiled at all
+            if !raw.contains_key("default") && !raw.contains_key(name) {
+                return raw.clone();
+            }
+            let mut resolved = match raw.get("default") {
+                Some(Value::Object(map)) => Value::Object(map.clone()),
+                _ => Value::Object(Map::new()),
+            };
+            if let Some(active) = raw.get(name) {
+                deep_merge(&mut resolved, active);
+            }
+            match resolved {
+                Value::Object(map) => map,
+                _ => Map::new(),
+            }
+        }
+        None => match raw.get("default") {
+            Some(Value::Object(map)) => map.clone(),
+            // no profiles in play: the whole root is the section map
+            _ => raw.clone(),
+        },
+    }
+}
+
+/// Reads a config file and returns its top-level object (the profile blocks), or an empty map when
+/// the file is missing or empty. Mirrors the root-must-be-an-object contract `load()` enforces.
+pub(crate) fn read_file_raw(
+    format: &Format,
+    path: &Path,
+) -> Result<Map<String, Value>, KonfigError> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+
+    let data = read(path).map_err(|err| KonfigError::LoadError(err.to_string()))?;
+    if data.is_empty() {
+        return Ok(Map::new());
+    }
+
+    let value: Value = format.create_handler().unmarshal(data.as_slice())?;
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => Err(KonfigError::LoadError(
+            "Config root must be an object".to_string(),
+        )),
+    }
+}
+
+/// Scans the process environment for variables shaped like `PREFIX<sep>SECTION<sep>FIELD` and folds
+/// them into a section map, so deployments can override any registered section without touching
+/// files. The section is the first segment and any further segments build a nested object, letting
+/// `MYAPP__Server__tls__port` reach a nested field.
+///
+/// Segments are matched against the Rust identifiers exactly and case-sensitively: the section
+/// segment must equal the registered section name (the struct ident, e.g. `Server`) and each field
+/// segment must equal the serde field name (typically lowercase). A mismatched case silently
+/// fails to override, so `MYAPP__server__port` will not touch a `Server` section.
+///
+/// Values are parsed leniently: a JSON scalar (number, bool, null) is kept as that scalar so
+/// `MYAPP__Server__port=8080` lands as a number, and anything else falls back to a string.
+pub(crate) fn env_sections(prefix: &str, separator: &str) -> Map<String, Value> {
+    let mut root = Map::new();
+    let full_prefix = format!("{}{}", prefix, separator);
+
+    for (key, value) in std::env::vars() {
+        let Some(rest) = key.strip_prefix(&full_prefix) else {
+            continue;
+        };
+
+        let mut parts = rest.split(separator).filter(|part| !part.is_empty());
+        let Some(section) = parts.next() else {
+            continue;
+        };
+        let path: Vec<&str> = parts.collect();
+        if path.is_empty() {
+            continue;
+        }
+
+        let section_entry = root
+            .entry(section.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        insert_nested(section_entry, &path, parse_env_scalar(&value));
+    }
+
+    root
+}
+
+/// Inserts `leaf` at `path` inside `target`, creating intermediate objects as needed.
+fn insert_nested(target: &mut Value, path: &[&str], leaf: Value) {
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let object = target.as_object_mut().expect("just coerced to object");
+
+    match path {
+        [key] => {
+            object.insert(key.to_string(), leaf);
+        }
+        [key, rest @ ..] => {
+            let child = object
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+            insert_nested(child, rest, leaf);
+        }
+        [] => {}
+    }
+}
+
+/// Parses an env value as a JSON scalar when it is one, otherwise keeps it as a string.
+fn parse_env_scalar(raw: &str) -> Value {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_) | Value::Null)) => value,
+        _ => Value::String(raw.to_string()),
+    }
+}
+
+/// Deep-merges `overlay` into `base`: objects are merged key by key, every other value (including
+/// arrays) replaces whatever is already there so the higher layer wins on scalar conflicts.
+pub(crate) fn deep_merge(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, value) in overlay_map {
+                deep_merge(base_map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => {
+            *base = overlay.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deep_merge_recurses_into_objects_and_overwrites_scalars() {
+        let mut base = json!({ "server": { "host": "localhost", "port": 80 }, "debug": false });
+        let overlay = json!({ "server": { "port": 8080 }, "debug": true });
+
+        deep_merge(&mut base, &overlay);
+
+        // nested object is merged key by key, higher layer wins on the conflicting scalar
+        assert_eq!(
+            base,
+            json!({ "server": { "host": "localhost", "port": 8080 }, "debug": true })
+        );
+    }
+
+    #[test]
+    fn deep_merge_replaces_arrays_wholesale() {
+        let mut base = json!({ "items": [1, 2, 3] });
+        deep_merge(&mut base, &json!({ "items": [9] }));
+        assert_eq!(base, json!({ "items": [9] }));
+    }
+
+    #[test]
+    fn resolve_merges_layers_in_precedence_order() {
+        let lower = ConfigLayer::new(
+            ConfigOrigin::Defaults,
+            Format::JSON,
+            json!({ "App": { "port": 80, "host": "default" } })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        let higher = ConfigLayer::new(
+            ConfigOrigin::Override,
+            Format::JSON,
+            json!({ "App": { "port": 8080 } })
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+
+        // deliberately pass the higher-precedence layer first to prove ordering is by rank
+        let resolved = resolve(&[higher, lower], &["App".to_string()]);
+        assert_eq!(resolved.len(), 1);
+        let (name, value, origins) = &resolved[0];
+        assert_eq!(name, "App");
+        assert_eq!(value, &json!({ "port": 8080, "host": "default" }));
+        assert_eq!(origins.get("port"), Some(&ConfigOrigin::Override));
+        assert_eq!(origins.get("host"), Some(&ConfigOrigin::Defaults));
+    }
+
+    #[test]
+    fn parse_env_scalar_keeps_json_scalars_else_string() {
+        assert_eq!(parse_env_scalar("8080"), json!(8080));
+        assert_eq!(parse_env_scalar("true"), json!(true));
+        assert_eq!(parse_env_scalar("null"), Value::Null);
+        // non-scalar JSON and plain text both fall back to a string
+        assert_eq!(parse_env_scalar("localhost"), json!("localhost"));
+        assert_eq!(parse_env_scalar("[1,2]"), json!("[1,2]"));
+    }
+
+    #[test]
+    fn env_sections_builds_nested_tree_under_section() {
+        // use a test-local prefix so we do not clash with the ambient environment
+        std::env::set_var("KONFIGTEST__Server__port", "8080");
+        std::env::set_var("KONFIGTEST__Server__tls__enabled", "true");
+        std::env::set_var("KONFIGTEST__Server__host", "example.com");
+
+        let sections = env_sections("KONFIGTEST", "__");
+
+        assert_eq!(
+            sections.get("Server"),
+            Some(&json!({
+                "port": 8080,
+                "host": "example.com",
+                "tls": { "enabled": true }
+            }))
+        );
+
+        std::env::remove_var("KONFIGTEST__Server__port");
+        std::env::remove_var("KONFIGTEST__Server__tls__enabled");
+        std::env::remove_var("KONFIGTEST__Server__host");
+    }
+
+    #[test]
+    fn resolve_profiles_treats_flat_root_as_sections_without_profiles() {
+        let raw = json!({ "Section": { "a": 1 } }).as_object().unwrap().clone();
+        // no active profile and no `default` block: the root is the section map
+        assert_eq!(resolve_profiles(&raw, None), raw);
+    }
+
+    #[test]
+    fn resolve_profiles_merges_active_over_default() {
+        let raw = json!({
+            "default": { "App": { "port": 80, "host": "base" } },
+            "dev": { "App": { "port": 3000 } }
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        let resolved = resolve_profiles(&raw, Some("dev"));
+        assert_eq!(
+            Value::Object(resolved),
+            json!({ "App": { "port": 3000, "host": "base" } })
+        );
+
+        // with no active profile, only the default block is used
+        let resolved = resolve_profiles(&raw, None);
+        assert_eq!(
+            Value::Object(resolved),
+            json!({ "App": { "port": 80, "host": "base" } })
+        );
+    }
+
+    #[test]
+    fn resolve_profiles_falls_back_to_flat_root_when_profile_not_in_file() {
+        // activating a profile against a legacy flat file must not drop its values
+        let raw = json!({ "Server": { "host": "localhost" } })
+            .as_object()
+            .unwrap()
+            .clone();
+        assert_eq!(resolve_profiles(&raw, Some("dev")), raw);
+    }
+}
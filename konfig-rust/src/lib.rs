@@ -1,14 +1,23 @@
 pub mod format;
+pub mod layer;
 
 use crate::format::*;
+use crate::layer::{ConfigLayer, ConfigOrigin};
+use serde_json::Value;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::error::Error;
-use std::fs::{read, File};
+use std::fs::File;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
-use std::str;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex, Once, OnceLock};
+use std::thread;
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+use signal_hook::low_level;
 use thiserror::Error;
 
 /// This is the error enum for konfig-rust, it contains all possible errors returned by konfig-rust
@@ -48,15 +57,16 @@ pub trait KonfigSection {
     fn on_load(&self) -> Result<(), KonfigError> {
         Ok(())
     }
-    fn to_bytes(&self, format: &FormatHandlerEnum) -> Result<Vec<u8>, KonfigError>;
+    fn to_bytes(&self, format: &FormatHandler) -> Result<Vec<u8>, KonfigError>;
     fn update_from_bytes(
         &mut self,
         bytes: &[u8],
-        format: &FormatHandlerEnum,
+        format: &FormatHandler,
     ) -> Result<(), KonfigError>;
 }
 
 // had to go into unsafe land to deliver dx 🤷
+#[derive(Clone, Copy)]
 struct SectionPtr {
     ptr: NonNull<dyn KonfigSection>,
 }
@@ -94,6 +104,19 @@ pub struct KonfigOptions {
     /// Path to the file used for configuration, if the file does not exist it will be created,
     /// the path can be absolute or relative
     pub config_path: String,
+    /// If `Some`, environment variables starting with this prefix are folded into the config as a
+    /// high-precedence layer on every `load()`, e.g. `MYAPP__Server__port` overrides the `port`
+    /// field of the `Server` section. Segments must match the registered section name and serde
+    /// field names exactly and case-sensitively. Leave `None` to disable environment overrides.
+    pub env_prefix: Option<String>,
+    /// The separator between the prefix and each section/field segment in an environment key,
+    /// conventionally `"__"` so single underscores can appear inside field names.
+    pub env_separator: String,
+    /// The active profile to merge over the `default` block of the config file, e.g. `Some("dev")`.
+    /// Leave `None` to use only the `default` block (a file without a `default` block is then read
+    /// as a flat section map). Switch at runtime with
+    /// [`KonfigManager::set_profile`].
+    pub profile: Option<String>,
 }
 
 /// The main manager in konfig-rust, this is intended to be created near the start of your program, and destroyed by closing it
@@ -123,6 +146,9 @@ pub struct KonfigOptions {
 ///     auto_save: true,
 ///     use_callbacks: true,
 ///     config_path: "config.json".to_string(),
+///     env_prefix: None,
+///     env_separator: "__".to_string(),
+///     profile: None,
 /// });
 ///
 /// manager.register_section(&mut c).unwrap();
@@ -135,9 +161,94 @@ pub struct KonfigOptions {
 /// ```
 pub struct KonfigManager {
     opts: KonfigOptions,
-    format_handler: FormatHandlerEnum,
+    format_handler: FormatHandler,
     path: Box<Path>,
     sections: HashMap<String, SectionPtr>,
+    /// The configuration stack, merged from lowest to highest precedence on every `load()`.
+    layers: Vec<ConfigLayer>,
+    /// Section name -> (top-level field -> origin of the highest layer that set it), populated by
+    /// `load()`. Only top-level keys are tracked; nested leaves inside a deep-merged object are not.
+    origins: HashMap<String, HashMap<String, ConfigOrigin>>,
+    /// Closure invoked when a background reload (see `watch`) fails; falls back to a `tracing`
+    /// error log when unset.
+    on_reload_error: Option<ReloadErrorHandler>,
+    /// Unique id used to key this manager's `auto_save` job in the process-global registry.
+    id: u64,
+}
+
+/// User-supplied handler for errors surfaced by the filesystem watcher started with `watch`.
+type ReloadErrorHandler = Arc<dyn Fn(&KonfigError) + Send + Sync>;
+
+/// Owns everything a background thread needs to re-run the layer merge into the live sections
+/// without borrowing the `KonfigManager`. The `SectionPtr`s are `Send + Sync` by construction, and
+/// the layer snapshot is refreshed from disk on every reload.
+struct ReloadJob {
+    sections: HashMap<String, SectionPtr>,
+    layers: Vec<ConfigLayer>,
+    format: Format,
+    path: PathBuf,
+    use_callbacks: bool,
+    env_prefix: Option<String>,
+    env_separator: String,
+}
+
+impl ReloadJob {
+    /// Re-reads the stack and swaps each section's state atomically: on a validation/on_load
+    /// failure the section is rolled back to its previous bytes so it is never left half-updated.
+    fn reload(&mut self) -> Result<(), KonfigError> {
+        // build the handler here rather than storing one: `FormatHandler::Custom` is not `Send`, so
+        // the job cannot carry it across the thread boundary — `Format` is cheap to clone instead
+        let handler = self.format.create_handler();
+
+        layer::refresh(
+            &mut self.layers,
+            &self.format,
+            self.env_prefix.as_deref(),
+            &self.env_separator,
+        )?;
+
+        let names: Vec<String> = self.sections.keys().cloned().collect();
+
+        for (name, merged, _origins) in layer::resolve(&self.layers, &names) {
+            if let Some(section_ptr) = self.sections.get_mut(&name) {
+                let bytes = handler.marshal(&merged)?;
+                unsafe {
+                    let section = section_ptr.as_mut();
+                    let previous = section.to_bytes(&handler)?;
+                    section.update_from_bytes(&bytes, &handler)?;
+                    if self.use_callbacks {
+                        if let Err(err) = section.validate().and_then(|_| section.on_load()) {
+                            // roll back to the last good state rather than leave it half-applied
+                            section.update_from_bytes(&previous, &handler)?;
+                            return Err(err);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps a filesystem watcher and its reload thread alive; dropping it stops watching and joins
+/// the thread. Returned by [`KonfigManager::watch`].
+///
+/// While this handle is live, sections are mutated in place by the reload thread without a lock;
+/// see the concurrency note on [`KonfigManager::watch`] before reading sections from other threads.
+pub struct WatchHandle {
+    _watcher: notify::RecommendedWatcher,
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
 }
 
 // lazy_static! {
@@ -146,27 +257,45 @@ pub struct KonfigManager {
 
 impl KonfigManager {
     /// Simply creates a new `KonfigManager`, with the passed in `KonfigOptions`
-    pub fn new(opts: KonfigOptions) -> Self {
+    ///
+    /// If the `config_path` extension maps to a known format — a builtin or one registered with
+    /// [`format::register_format`] — that format is picked up automatically, so a registered custom
+    /// handler (e.g. INI or RON) flows through `load`/`save` without the caller restating it.
+    /// `opts.format` is used as the fallback when the extension is unknown.
+    pub fn new(mut opts: KonfigOptions) -> Self {
+        if let Some(detected) = Format::from_path(&opts.config_path) {
+            opts.format = detected;
+        }
+        let path = Box::from(Path::new(&opts.config_path));
+        // the primary config file is the one writable layer; defaults/system layers added later
+        // sit below it and overrides above it.
+        let primary = ConfigLayer::writable_file(
+            ConfigOrigin::File(opts.config_path.clone().into()),
+            opts.format.clone(),
+            opts.profile.clone(),
+        );
         let m = KonfigManager {
             format_handler: opts.format.create_handler(),
-            path: Box::from(Path::new(&opts.config_path)),
+            path,
             opts,
             sections: HashMap::new(),
+            layers: vec![primary],
+            origins: HashMap::new(),
+            on_reload_error: None,
+            id: NEXT_JOB_ID.fetch_add(1, Ordering::SeqCst),
         };
 
-        // probably just gonna rawdog pointers here to, couse rust cries too much about it
         if m.opts.auto_save {
-            // setup panic hook
-            // let prev_hook = panic::take_hook();
-            // panic::set_hook(Box::new(move |panic_info| {
-            //     &m.save().unwrap();
-            //     prev_hook(panic_info);
-            // }));
-
-            // TODO: setup fully later
-            // setup SIGINT and SIGTERM
-            // let mut signals = Signals::new(&[signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM]).unwrap();
-
+            // install the shared panic/signal hooks once, then register an (initially empty) job
+            // that `register_section` keeps in sync as sections come in
+            install_save_hooks();
+            save_registry().lock().unwrap().push(SaveJob {
+                id: m.id,
+                sections: HashMap::new(),
+                path: m.path.to_path_buf(),
+                format: m.opts.format.clone(),
+                profile: m.opts.profile.clone(),
+            });
         }
 
         m
@@ -179,25 +308,23 @@ impl KonfigManager {
         if File::open(&self.path).is_err() {
             File::create(&self.path).map_err(|err| KonfigError::LoadError(err.to_string()))?;
         }
-        let data = read(&self.path).map_err(|err| KonfigError::LoadError(err.to_string()))?;
 
-        if data.is_empty() {
-            return Ok(());
-        }
+        // bring every file layer and the environment layer up to date, then merge the stack
+        layer::refresh(
+            &mut self.layers,
+            &self.opts.format,
+            self.opts.env_prefix.as_deref(),
+            &self.opts.env_separator,
+        )?;
 
-        let config: serde_json::Value = match &self.format_handler {
-            FormatHandlerEnum::JSON(handler) => handler.unmarshal(data.as_slice())?,
-            FormatHandlerEnum::YAML(handler) => handler.unmarshal(data.as_slice())?,
-            FormatHandlerEnum::TOML(handler) => handler.unmarshal(data.as_slice())?,
-        };
+        let names: Vec<String> = self.sections.keys().cloned().collect();
+        self.origins.clear();
 
-        let config_map = config
-            .as_object()
-            .ok_or_else(|| KonfigError::LoadError("Config root must be an object".to_string()))?;
+        for (name, merged, origins) in layer::resolve(&self.layers, &names) {
+            self.origins.insert(name.clone(), origins);
 
-        for (name, section_value) in config_map {
-            if let Some(section_ptr) = self.sections.get_mut(name) {
-                let bytes = self.format_handler.marshal(section_value)?;
+            if let Some(section_ptr) = self.sections.get_mut(&name) {
+                let bytes = self.format_handler.marshal(&merged)?;
                 unsafe {
                     let section = section_ptr.as_mut();
                     section.update_from_bytes(&bytes, &self.format_handler)?;
@@ -212,6 +339,148 @@ impl KonfigManager {
         Ok(())
     }
 
+    /// Adds a configuration layer read from `path` in the given `format`.
+    ///
+    /// Layers are merged in precedence order on the next `load()`; the rank is derived from
+    /// `origin` (defaults lowest, programmatic overrides highest). The primary config file passed
+    /// to [`KonfigManager::new`] is always present as the single writable layer, so added layers
+    /// only contribute values and are never written back to by `save()`.
+    ///
+    /// A layer added with [`ConfigOrigin::Environment`] is discarded on the next `load()`, which
+    /// rebuilds the environment layer from `env_prefix` alone.
+    pub fn add_layer(
+        &mut self,
+        origin: ConfigOrigin,
+        format: Format,
+        path: impl AsRef<Path>,
+    ) -> Result<(), KonfigError> {
+        let raw = layer::read_file_raw(&format, path.as_ref())?;
+        let sections = layer::resolve_profiles(&raw, self.opts.profile.as_deref());
+        let mut layer = ConfigLayer::new(origin, format, sections);
+        layer.profile = self.opts.profile.clone();
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    /// Returns the origin of the layer that won `key` in `section` during the last `load()`,
+    /// or `None` if the section or key was not set by any layer.
+    ///
+    /// Granularity is the section's **top-level** field: `key` is a direct field of the section,
+    /// not a dotted path into a nested object. The reported origin is the highest-precedence layer
+    /// that set that top-level key, even when the value is an object that was deep-merged from
+    /// several layers — so `origin_of("server", ..)` points at the layer that last touched
+    /// `server`, not necessarily the one that supplied every nested leaf inside it.
+    pub fn origin_of(&self, section: &str, key: &str) -> Option<ConfigOrigin> {
+        self.origins.get(section)?.get(key).cloned()
+    }
+
+    /// Switches the active profile and re-resolves every registered section in place.
+    ///
+    /// File layers are retargeted at the new profile's block and the stack is re-merged via
+    /// `load()`, so a value set only in the previous profile falls back to `default` (or disappears
+    /// if `default` does not set it).
+    ///
+    /// Throws: `KonfigError::LoadError`
+    pub fn set_profile(&mut self, name: impl Into<String>) -> Result<(), KonfigError> {
+        let profile = Some(name.into());
+        self.opts.profile = profile.clone();
+        for layer in &mut self.layers {
+            if matches!(layer.origin, ConfigOrigin::File(_)) {
+                layer.profile = profile.clone();
+            }
+        }
+        // keep the auto_save job writing into the newly active profile's block
+        if self.opts.auto_save {
+            let mut registry = save_registry().lock().unwrap();
+            if let Some(job) = registry.iter_mut().find(|job| job.id == self.id) {
+                job.profile = profile.clone();
+            }
+        }
+        self.load()
+    }
+
+    /// Registers a closure that receives any error raised while a background reload (see `watch`)
+    /// is in flight, instead of the default `tracing` error log.
+    pub fn on_reload_error<F>(&mut self, handler: F)
+    where
+        F: Fn(&KonfigError) + Send + Sync + 'static,
+    {
+        self.on_reload_error = Some(Arc::new(handler));
+    }
+
+    /// Watches the config file and reloads the registered sections whenever it changes.
+    ///
+    /// A `notify` watcher runs on a background thread; change events are debounced and then fed
+    /// through the same layer merge as `load()`. A reload that fails validation rolls the affected
+    /// section back to its previous bytes (see [`ReloadJob::reload`]); the error is then passed to
+    /// the closure set with [`KonfigManager::on_reload_error`] or logged via `tracing`.
+    ///
+    /// The returned [`WatchHandle`] must be kept alive for watching to continue; dropping it stops
+    /// the watcher and joins its thread.
+    ///
+    /// # Concurrency
+    ///
+    /// The reload thread writes each section in place through a raw pointer with no lock, so the
+    /// rollback only makes a reload atomic with respect to validation, **not** with respect to
+    /// other threads. While a [`WatchHandle`] is live the application must not read a registered
+    /// section concurrently with a reload, or it risks a data race (undefined behaviour). If a
+    /// section must be read from another thread, make its own fields synchronized (e.g. wrap them
+    /// in atomics or a `Mutex`) so the in-place swap stays sound.
+    ///
+    /// Throws: `KonfigError::LoadError`
+    pub fn watch(&mut self) -> Result<WatchHandle, KonfigError> {
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })
+        .map_err(|err| KonfigError::LoadError(err.to_string()))?;
+
+        watcher
+            .watch(&self.path, RecursiveMode::NonRecursive)
+            .map_err(|err| KonfigError::LoadError(err.to_string()))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let on_error = self.on_reload_error.clone();
+        let mut job = ReloadJob {
+            sections: self.sections.clone(),
+            layers: self.layers.clone(),
+            format: self.opts.format.clone(),
+            path: self.path.to_path_buf(),
+            use_callbacks: self.opts.use_callbacks,
+            env_prefix: self.opts.env_prefix.clone(),
+            env_separator: self.opts.env_separator.clone(),
+        };
+
+        let handle = thread::spawn(move || {
+            let debounce = Duration::from_millis(200);
+            while !thread_stop.load(Ordering::SeqCst) {
+                match rx.recv_timeout(debounce) {
+                    Ok(Ok(_event)) => {
+                        // collapse the burst of events that a single save produces
+                        while rx.recv_timeout(debounce).is_ok() {}
+                        match job.reload() {
+                            Ok(()) => tracing::info!("konfig reloaded from {:?}", job.path),
+                            Err(err) => match &on_error {
+                                Some(handler) => handler(&err),
+                                None => tracing::error!("konfig reload failed: {}", err),
+                            },
+                        }
+                    }
+                    Ok(Err(err)) => tracing::warn!("konfig watch error: {}", err),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+        });
+
+        Ok(WatchHandle {
+            _watcher: watcher,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
     // fn internal_save(&self) {
     //     let mut closures = SAVE_CLOSURE.lock().unwrap();
     //     while let Some(closure) = closures.pop() {
@@ -219,40 +488,17 @@ impl KonfigManager {
     //     }
     // }
 
-    /// Saves the registered sections to the specified file
+    /// Saves the registered sections to the specified file, writing only into the active profile's
+    /// block and leaving every other profile in the file untouched so profiles round-trip.
     ///
     /// Throws: `KonfigError::SaveError`
     pub fn save(&self) -> Result<(), KonfigError> {
-        let mut map: HashMap<String, serde_json::Value> = HashMap::new();
-
-        for (name, section_ptr) in &self.sections {
-            let section = unsafe { section_ptr.as_ref() };
-            let bytes = section.to_bytes(&self.format_handler)?;
-
-            let value: serde_json::Value = match &self.format_handler {
-                FormatHandlerEnum::JSON(_) => serde_json::from_slice(&bytes)
-                    .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?,
-                FormatHandlerEnum::YAML(_) => serde_yaml::from_slice(&bytes)
-                    .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?,
-                FormatHandlerEnum::TOML(_) => {
-                    let s = str::from_utf8(&bytes)
-                        .map_err(|err| KonfigError::UnmarshalError(err.to_string()))?;
-                    toml::from_str(s).map_err(|err| KonfigError::UnmarshalError(err.to_string()))?
-                }
-            };
-
-            map.insert(name.clone(), value);
-        }
-
-        let out = self.format_handler.marshal(&map)?;
-
-        let mut f =
-            File::create(&self.path).map_err(|err| KonfigError::SaveError(err.to_string()))?;
-
-        f.write_all(out.as_slice())
-            .map_err(|err| KonfigError::SaveError(err.to_string()))?;
-
-        Ok(())
+        write_sections(
+            &self.sections,
+            &self.path,
+            &self.opts.format,
+            self.opts.profile.as_deref(),
+        )
     }
 
     /// Registers a new section with the KonfigManager, the section must use the `Serialize` and `Deserialize` macros
@@ -275,6 +521,15 @@ impl KonfigManager {
         let section_ptr = SectionPtr::new(section);
 
         self.sections.insert(name, section_ptr);
+
+        // keep this manager's auto_save job pointing at the full, current section set
+        if self.opts.auto_save {
+            let mut registry = save_registry().lock().unwrap();
+            if let Some(job) = registry.iter_mut().find(|job| job.id == self.id) {
+                job.sections = self.sections.clone();
+            }
+        }
+
         Ok(())
     }
 
@@ -291,6 +546,138 @@ impl KonfigManager {
     }
 }
 
+impl Drop for KonfigManager {
+    fn drop(&mut self) {
+        // deregister the auto_save job so the panic/signal hooks never read section pointers that
+        // have outlived the sections they point at
+        if self.opts.auto_save {
+            save_registry()
+                .lock()
+                .unwrap()
+                .retain(|job| job.id != self.id);
+        }
+    }
+}
+
+/// Serializes every section and writes it into the active profile block of `path`, leaving other
+/// profiles untouched. Shared by [`KonfigManager::save`] and the auto_save [`SaveJob::flush`] path.
+///
+/// # Safety
+///
+/// Reads each section through its `SectionPtr`, so every pointer must still be valid; the registry
+/// is kept honest by deregistering jobs on drop.
+fn write_sections(
+    sections: &HashMap<String, SectionPtr>,
+    path: &Path,
+    format: &Format,
+    profile: Option<&str>,
+) -> Result<(), KonfigError> {
+    let handler = format.create_handler();
+    let mut map: serde_json::Map<String, Value> = serde_json::Map::new();
+
+    for (name, section_ptr) in sections {
+        let section = unsafe { section_ptr.as_ref() };
+        let bytes = section.to_bytes(&handler)?;
+
+        // round-trip through the handler's own parser so custom formats work like the builtins
+        let value: Value = handler.unmarshal(&bytes)?;
+
+        map.insert(name.clone(), value);
+    }
+
+    // splice into the active profile block, preserving the other profiles already on disk; with no
+    // active profile, write the active `default` block if the file uses profiles, otherwise keep
+    // the legacy flat layout so profile-free files round-trip unchanged
+    let mut raw = layer::read_file_raw(format, path)?;
+    let out = match profile {
+        Some(name) => {
+            raw.insert(name.to_string(), Value::Object(map));
+            handler.marshal(&raw)?
+        }
+        None if raw.contains_key("default") => {
+            raw.insert("default".to_string(), Value::Object(map));
+            handler.marshal(&raw)?
+        }
+        None => handler.marshal(&map)?,
+    };
+
+    let mut f = File::create(path).map_err(|err| KonfigError::SaveError(err.to_string()))?;
+    f.write_all(out.as_slice())
+        .map_err(|err| KonfigError::SaveError(err.to_string()))?;
+
+    Ok(())
+}
+
+/// One registered manager's worth of state, enough to serialize and flush its sections from a
+/// panic hook or signal handler without borrowing the manager itself.
+struct SaveJob {
+    id: u64,
+    sections: HashMap<String, SectionPtr>,
+    path: PathBuf,
+    format: Format,
+    profile: Option<String>,
+}
+
+impl SaveJob {
+    fn flush(&self) -> Result<(), KonfigError> {
+        write_sections(&self.sections, &self.path, &self.format, self.profile.as_deref())
+    }
+}
+
+/// Hands out the monotonic ids used to key [`SaveJob`]s.
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Set once the first flush runs so the panic hook and the signal handler never write twice.
+static FLUSHED: AtomicBool = AtomicBool::new(false);
+
+/// Process-global list of auto_save jobs, one per manager created with `auto_save: true`.
+fn save_registry() -> &'static Mutex<Vec<SaveJob>> {
+    static REGISTRY: OnceLock<Mutex<Vec<SaveJob>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Flushes every registered job exactly once; later calls (e.g. a signal arriving after a panic)
+/// are no-ops thanks to the [`FLUSHED`] guard.
+fn flush_all_jobs() {
+    if FLUSHED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    for job in save_registry().lock().unwrap().iter() {
+        if let Err(err) = job.flush() {
+            tracing::error!("konfig auto_save failed for {:?}: {}", job.path, err);
+        }
+    }
+}
+
+/// Installs the panic hook and SIGINT/SIGTERM handler thread a single time for the whole process.
+fn install_save_hooks() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            flush_all_jobs();
+            previous(info);
+        }));
+
+        match signal_hook::iterator::Signals::new([
+            signal_hook::consts::SIGINT,
+            signal_hook::consts::SIGTERM,
+        ]) {
+            Ok(mut signals) => {
+                thread::spawn(move || {
+                    for signal in signals.forever() {
+                        flush_all_jobs();
+                        // re-raise the default disposition so the process still terminates
+                        let _ = low_level::emulate_default_handler(signal);
+                    }
+                });
+            }
+            Err(err) => tracing::error!("konfig could not install signal handler: {}", err),
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +713,9 @@ mod tests {
             auto_save: false,
             use_callbacks: true,
             config_path: "test.json".to_string(),
+            env_prefix: None,
+            env_separator: "__".to_string(),
+            profile: None,
         });
 
         mngr.register_section(&mut t)